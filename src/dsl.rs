@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use lalrpop_util::{lalrpop_mod, ParseError as LalrpopError};
+
+use crate::Config;
+
+lalrpop_mod!(
+    #[allow(clippy::all)]
+    phonology
+);
+
+/// A syntax error in a phonology DSL source, carrying a line and column so
+/// the user is pointed at the offending directive instead of being handed a
+/// bare `unwrap()` panic.
+#[derive(Debug)]
+pub struct ParseError {
+    /// The 1-based line the error was found on, when it maps to one
+    pub line: Option<usize>,
+    /// The 1-based column the error was found at, when it maps to one
+    pub column: Option<usize>,
+    /// A human-readable description of what went wrong
+    pub message: String,
+}
+
+impl ParseError {
+    /// Builds an error anchored to a source position.
+    fn at(line: usize, column: usize, message: String) -> ParseError {
+        ParseError { line: Some(line), column: Some(column), message }
+    }
+
+    /// Builds an error that has no meaningful position, e.g. a required
+    /// directive that is absent from the file entirely.
+    fn whole_file(message: String) -> ParseError {
+        ParseError { line: None, column: None, message }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => {
+                write!(f, "syntax error at line {}, column {}: {}", line, column, self.message)
+            }
+            _ => write!(f, "config error: {}", self.message),
+        }
+    }
+}
+
+/// One directive produced by the grammar, before it is folded into a
+/// [`Config`]. The grammar is deliberately uniform — every non-blank line is
+/// one or more words, an `=`, and zero or more value words — so the mapping
+/// onto config fields lives here rather than in the grammar.
+pub enum Directive {
+    /// `key = values...`
+    Assignment(String, Vec<String>),
+    /// `romanize <ipa> = <text>`
+    Romanize(String, String),
+}
+
+impl Directive {
+    /// Classifies a parsed line into the directive it represents.
+    pub fn new(keys: Vec<String>, vals: Vec<String>) -> Directive {
+        if keys.len() >= 2 && keys[0] == "romanize" {
+            Directive::Romanize(keys[1].clone(), vals.join(" "))
+        } else {
+            Directive::Assignment(keys[0].clone(), vals)
+        }
+    }
+}
+
+/// Converts a byte offset into the source into a 1-based line and column.
+fn locate(source: &str, offset: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut column = 1;
+    for (index, c) in source.char_indices() {
+        if index >= offset {
+            break;
+        }
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    (line, column)
+}
+
+/// Parses a line-oriented phonology DSL into a [`Config`].
+///
+/// The source is parsed by a generated LALRPOP grammar (see
+/// `src/phonology.lalrpop`): one directive per line, with blank lines and
+/// lines beginning with `#` treated as comments. List directives take
+/// whitespace-separated tokens, `romanize <ipa> = <text>` adds a romanization
+/// mapping, and `rule` / `constraint` each take the words after the `=`,
+/// rejoined with single spaces (so neither can contain an `=` of its own):
+///
+/// ```text
+/// # a tiny phonology
+/// consonants = p t k m n s
+/// vowels = a e i o u
+/// onsets = @
+/// codas = n s
+/// structures = CV CVC V
+/// stressed = 0
+/// max_syllables = 3
+/// romanize ʃ = sh
+/// rule = p > b / V _ V
+/// constraint = (\[.\])\1
+/// ```
+///
+/// # Arguments
+///
+/// * `source` - The full DSL source text
+///
+/// # Returns
+///
+/// `Result<Config, ParseError>` - The parsed config, or the first error
+/// encountered, with its line and column when it has one (a required
+/// directive that is absent entirely has none)
+pub fn parse(source: &str) -> Result<Config, ParseError> {
+    let directives = phonology::ProgramParser::new().parse(source).map_err(|err| {
+        let offset = match err {
+            LalrpopError::InvalidToken { location } => location,
+            LalrpopError::UnrecognizedEof { location, .. } => location,
+            LalrpopError::UnrecognizedToken { token, .. } => token.0,
+            LalrpopError::ExtraToken { token } => token.0,
+            LalrpopError::User { .. } => 0,
+        };
+        let (line, column) = locate(source, offset);
+        ParseError::at(line, column, "unexpected token in directive".to_string())
+    })?;
+
+    let mut config = Config {
+        consonants: vec![],
+        onsets: vec![],
+        codas: vec![],
+        vowels: vec![],
+        stressed: 0,
+        romanization: HashMap::new(),
+        structures: vec![],
+        max_syllable_count: 0,
+        rules: vec![],
+        constraints: vec![],
+    };
+
+    // remember where `structures` and `max_syllables` were declared so
+    // validation errors can point back at the real offender
+    let mut structures_loc = None;
+    let mut max_loc = None;
+
+    for (offset, directive) in directives {
+        let (line, column) = locate(source, offset);
+        match directive {
+            Directive::Romanize(key, value) => {
+                config.romanization.insert(key, value);
+            }
+            Directive::Assignment(key, vals) => match key.as_str() {
+                "consonants" => config.consonants = vals,
+                "vowels" => config.vowels = vals,
+                "onsets" => config.onsets = vals,
+                "codas" => config.codas = vals,
+                "structures" | "structure" => {
+                    structures_loc = Some((line, column));
+                    config.structures = vals;
+                }
+                "stressed" => config.stressed = parse_int(&vals, line, column)?,
+                "max_syllables" | "max_syllable_count" => {
+                    max_loc = Some((line, column));
+                    config.max_syllable_count = parse_int(&vals, line, column)?
+                }
+                "rule" => config.rules.push(vals.join(" ")),
+                "constraint" => config.constraints.push(vals.join(" ")),
+                other => {
+                    return Err(ParseError::at(line, column, format!("unknown directive '{}'", other)))
+                }
+            },
+        }
+    }
+
+    validate(&config, structures_loc, max_loc)?;
+    Ok(config)
+}
+
+/// Checks that the parsed config has everything the generator needs, so an
+/// incomplete hand-authored file is rejected up front instead of panicking
+/// deep in word generation.
+///
+/// A directive that is present but wrong is reported at its own location; a
+/// directive that is absent entirely has no location to report.
+fn validate(
+    config: &Config,
+    structures_loc: Option<(usize, usize)>,
+    max_loc: Option<(usize, usize)>,
+) -> Result<(), ParseError> {
+    let required = [
+        ("consonants", config.consonants.is_empty()),
+        ("vowels", config.vowels.is_empty()),
+        ("structures", config.structures.is_empty()),
+    ];
+    for (name, missing) in required {
+        if missing {
+            return Err(ParseError::whole_file(format!("missing required directive '{}'", name)));
+        }
+    }
+
+    // `rng.gen_range(1..max + 1)` panics on an empty range, so a word must be
+    // allowed at least one syllable
+    if config.max_syllable_count < 1 {
+        let message = "'max_syllables' must be set to at least 1".to_string();
+        return Err(match max_loc {
+            Some((line, column)) => ParseError::at(line, column, message),
+            None => ParseError::whole_file(message),
+        });
+    }
+
+    // every structure needs a vowel, and any consonant slot it declares needs a
+    // corresponding non-empty onset/coda list to draw from
+    let (line, column) = structures_loc.unwrap_or((1, 1));
+    for structure in &config.structures {
+        let structure = structure.to_lowercase();
+        let vowel_index = match structure.find('v') {
+            Some(index) => index,
+            None => {
+                return Err(ParseError::at(line, column, format!("structure '{}' has no vowel", structure)))
+            }
+        };
+        if vowel_index > 0 && config.onsets.is_empty() {
+            return Err(ParseError::at(
+                line,
+                column,
+                format!("structure '{}' needs an onset but 'onsets' is empty", structure),
+            ));
+        }
+        if vowel_index < structure.len() - 1 && config.codas.is_empty() {
+            return Err(ParseError::at(
+                line,
+                column,
+                format!("structure '{}' needs a coda but 'codas' is empty", structure),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses the value words of an integer directive, reporting its position on
+/// failure.
+fn parse_int(vals: &[String], line: usize, column: usize) -> Result<i32, ParseError> {
+    let joined = vals.join(" ");
+    joined
+        .parse::<i32>()
+        .map_err(|_| ParseError::at(line, column, format!("expected an integer, found '{}'", joined)))
+}
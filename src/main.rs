@@ -1,10 +1,13 @@
 use std::{collections::HashMap, path::Path};
-use std::env;
 use std::fs::File;
-use rand::{Rng, prelude::ThreadRng, seq::SliceRandom};
-use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use clap::{Parser, ValueEnum};
+use rand::{Rng, rngs::StdRng, seq::SliceRandom, SeedableRng};
+use fancy_regex::Regex;
+use serde::{Deserialize, Serialize};
 
-static mut DEBUG: bool = false;
+/// Whether the program is running in debug mode, set once from the CLI flags.
+static DEBUG: AtomicBool = AtomicBool::new(false);
 
 // TODO: improve performance by pre-generating all possible syllables in a file, only updating them when the config file changes
 
@@ -22,77 +25,52 @@ static mut DEBUG: bool = false;
 /// ```
 macro_rules! debug {
     ($($arg:tt)*) => {
-        if unsafe { DEBUG } {
+        if crate::DEBUG.load(std::sync::atomic::Ordering::Relaxed) {
             eprint!("[DEBUG]   ");
             eprintln!($($arg)*);
         }
     }
 }
 
-/// Handles the processing of launch arguments passed in the command line
-/// 
-/// # Arguments
-/// 
-/// * `args` - The arguments passed in the command line
-/// 
-/// * `word_count` - The number of words to generate
-/// 
-/// * `path` - The path to the config file
-/// 
-/// # Returns
-/// 
-/// `bool` - Whether the program should continue running
-fn handle_launch_args(args: Vec<String>, word_count: &mut i32, path: &mut String, affixes: &mut Vec<String>) -> bool {
-    if args.len() > 1 {
-        // help message
-        if args.contains(&"-h".to_string()) || args.contains(&"--help".to_string()) {
-            println!("Usage: ./namesmith [-n <num_of_words>] [-d] [-p <path>]");
-            println!("\t-n\tnumber of words to generate");
-            println!("\t-d\tenable debug mode");
-            println!("\t-p\tpath to config file");
-            println!("\t-a\ta list of affixed syllables in as phonemes (e.g. \"-É™n,+pri\")");
-            println!("\t-v\tdisplay the current version");
-            println!("\t-h\tdisplay this help message");
-            return false;
-        }
-
-        // number of words to generate
-        if args.contains(&"-n".to_string()) || args.contains(&"--number".to_string()) {
-            let index = args.iter().position(|x| x == "-n").unwrap();
-            *word_count = args[index + 1].parse::<i32>().unwrap();
-        }
-
-        // enable debug mode
-        if args.contains(&"-d".to_string()) || args.contains(&"--debug".to_string()) {
-            unsafe {
-                DEBUG = true;
-            }
-        }
-
-        // path to config file
-        if args.contains(&"-p".to_string()) || args.contains(&"--path".to_string()) {
-            let index = args.iter().position(|x| x == "-p").unwrap();
-            *path = args[index + 1].clone();
-        }
-
-        // affixes
-        if args.contains(&"-a".to_string()) || args.contains(&"--affixes".to_string()) {
-            let index = args.iter().position(|x| x == "-a").unwrap();
-            *affixes = args[index + 1].clone().replace("\"", "").replace("'", "").split(",").map(|x| x.to_string()).collect();
-            debug!("Affixes: {:?}", affixes);
-        }
-
-        // version
-        if args.contains(&"-v".to_string()) || args.contains(&"--version".to_string()) {
-            println!("namesmith v{}", env!("CARGO_PKG_VERSION"));
-            return false;
-        }
-    } else {
-        println!("Usage: ./namesmith [-n <word_count>] [-d] [-p <path>]");
-        return false;
-    }
+mod dsl;
+mod rules;
+mod weighted;
+
+use weighted::WeightedList;
+
+/// The output format for generated words.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// The default `romanized /ipa/` listing, one word per line
+    Plain,
+    /// A JSON array of objects with `ipa` and `romanized` fields
+    Json,
+    /// Two comma-separated columns: `ipa` and `romanized`
+    Csv,
+}
 
-    return true;
+/// The launch arguments parsed from the command line.
+#[derive(Parser, Debug)]
+#[command(name = "namesmith", version)]
+struct Args {
+    /// Number of words to generate
+    #[arg(short = 'n', long = "count", alias = "number", default_value_t = 5)]
+    count: i32,
+    /// Enable debug mode
+    #[arg(short = 'd', long = "debug")]
+    debug: bool,
+    /// Path to the config file
+    #[arg(short = 'p', long = "path", default_value = "")]
+    path: String,
+    /// A list of affixed syllables as phonemes (e.g. "-ən,+pri")
+    #[arg(short = 'a', long = "affixes", value_delimiter = ',')]
+    affixes: Vec<String>,
+    /// Seed for the RNG so output is reproducible across runs
+    #[arg(long = "seed")]
+    seed: Option<u64>,
+    /// Output format
+    #[arg(long = "format", value_enum, default_value_t = Format::Plain)]
+    format: Format,
 }
 
 /// Handles the processing of the config file
@@ -126,11 +104,27 @@ fn handle_config(mut path: String) -> (Config, Vec<String>, Vec<String>) {
             romanization: HashMap::new(),
             structures: vec![],
             max_syllable_count: 0,
+            rules: vec![],
+            constraints: vec![],
         };
         return (_c, vec![], vec![]);
     }
 
-    let config: Config = serde_json::from_reader(File::open(&path).unwrap()).unwrap();
+    // pick the parser by file extension: `.json` keeps the original JSON
+    // format, anything else is treated as the line-oriented phonology DSL
+    let is_json = Path::new(&path).extension().map_or(false, |ext| ext == "json");
+    let config: Config = if is_json {
+        serde_json::from_reader(File::open(&path).unwrap()).unwrap()
+    } else {
+        let source = std::fs::read_to_string(&path).unwrap();
+        match dsl::parse(&source) {
+            Ok(config) => config,
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+    };
     let mut codas = config.codas.clone();
     let mut onsets = config.onsets.clone();
     if onsets.len() == 1 && onsets[0] == "@" {
@@ -151,30 +145,28 @@ fn wrap_sound(sound: String) -> String {
 /// # Arguments
 /// 
 /// * `structure` - The structure of the syllable (e.g. "cvc")
-/// 
-/// * `config` - The configuration loaded from the config file
-/// 
+///
 /// * `rng` - The random number generator
-/// 
+///
 /// * `word` - The word to generate the syllable for
-/// 
-/// * `vowel_index` - Where the vowel is located in the syllable
-/// 
-/// * `onsets` - A Vec of possible onsets to use
-/// 
-/// * `codas` - A Vec of possible codas to use
-/// 
+///
+/// * `onsets` - The weighted list of possible onsets to use
+///
+/// * `codas` - The weighted list of possible codas to use
+///
+/// * `vowels` - The weighted list of possible vowels to use
+///
 /// # Returns
-/// 
+///
 /// `String` - The generated syllable
-/// 
+///
 /// # Example
-/// 
+///
 /// ```
 /// // Will generate a random syllable
-/// build_syllable(syllable_out, &config, &mut rng, &word, 0, &onsets, &codas);
+/// build_syllable(&structure, &mut rng, &mut word, &onsets, &codas, &vowels);
 /// ```
-fn build_syllable(structure: &String, config: &Config, rng: &mut ThreadRng, word: &mut Vec<String>, onsets: &Vec<String>, codas: &Vec<String>) {
+fn build_syllable(structure: &String, rng: &mut StdRng, word: &mut Vec<String>, onsets: &WeightedList, codas: &WeightedList, vowels: &WeightedList) {
     let mut syllable: Vec<String> = vec![];
     // find the location of the vowel in the syllable
     let vowel_index = structure.to_lowercase().find("v").unwrap();
@@ -183,8 +175,8 @@ fn build_syllable(structure: &String, config: &Config, rng: &mut ThreadRng, word
         debug!("index:\t{}\tsyllable:\t{:?}", index, syllable);
         // if the letter is a vowel
         if structure.chars().nth(index).unwrap() == 'v' {
-            // choose a random vowel
-            let vowel = config.vowels.choose(rng).unwrap().to_owned();
+            // choose a weighted vowel
+            let vowel = vowels.choose(rng).to_owned();
             debug!("vowel:\t{}", vowel);
 
             syllable.push(wrap_sound(vowel.to_string()));
@@ -194,8 +186,8 @@ fn build_syllable(structure: &String, config: &Config, rng: &mut ThreadRng, word
 
             // if before v:
             if index < vowel_index {
-                // choose a random onset
-                let onset = onsets.choose(rng).unwrap();
+                // choose a weighted onset
+                let onset = onsets.choose(rng);
                 debug!("onset:\t{}", onset);
 
                 // insert the chosen onset before the vowel
@@ -207,8 +199,8 @@ fn build_syllable(structure: &String, config: &Config, rng: &mut ThreadRng, word
                 }
             }
             else {
-                // choose a random coda
-                let coda = codas.choose(rng).unwrap();
+                // choose a weighted coda
+                let coda = codas.choose(rng);
                 debug!("coda:\t{}", coda);
                 syllable.push(wrap_sound(coda.to_string()));
             }
@@ -225,16 +217,21 @@ fn build_syllable(structure: &String, config: &Config, rng: &mut ThreadRng, word
 /// 
 /// * `config` - The configuration loaded from the config file
 /// 
-/// * `onsets` - A Vec of possible onsets to use
-/// 
-/// * `codas` - A Vec of possible codas to use
-/// 
+/// * `onsets` - The weighted list of possible onsets to use
+///
+/// * `codas` - The weighted list of possible codas to use
+///
+/// * `vowels` - The weighted list of possible vowels to use
+///
+/// * `structures` - The weighted list of possible syllable structures to use
+///
+/// * `rng` - The random number generator threaded down from `main`
+///
 /// # Returns
-/// 
+///
 /// `Vec<String>` - The generated word as a Vec of Strings to account for dipthongs
-fn create_word(config: &Config, onsets: &Vec<String>, codas: &Vec<String>, affixes: &Vec<String>) -> Vec<String> {
+fn create_word(config: &Config, onsets: &WeightedList, codas: &WeightedList, vowels: &WeightedList, structures: &WeightedList, affixes: &Vec<String>, rng: &mut StdRng) -> Vec<String> {
     let mut word: Vec<String> = vec![];
-    let mut rng = rand::thread_rng();
     let syllable_count = rng.gen_range(1..config.max_syllable_count + 1);
     debug!("syllable_count:\t{}", syllable_count);
     // build the syllables
@@ -244,11 +241,11 @@ fn create_word(config: &Config, onsets: &Vec<String>, codas: &Vec<String>, affix
             word.push("'".to_owned());
         }
         // choose a syllable
-        let syllable_structure = config.structures.choose(&mut rng).unwrap();
+        let syllable_structure = structures.choose(rng);
         debug!("structure:\t{}", syllable_structure);
 
         // for each letter in the syllable
-        build_syllable(&syllable_structure.to_lowercase(), config, &mut rng, &mut word, onsets, codas);
+        build_syllable(&syllable_structure.to_lowercase(), rng, &mut word, onsets, codas, vowels);
 
         // unless it's the last syllable, add a syllable marker
         if i != syllable_count - 1 {
@@ -276,7 +273,7 @@ fn create_word(config: &Config, onsets: &Vec<String>, codas: &Vec<String>, affix
             // choose a random suffix
             let mut affix = "".to_owned();
             while !affix.starts_with("+") {
-                affix = copy.choose(&mut rng).unwrap().to_owned();
+                affix = copy.choose(rng).unwrap().to_owned();
                 // remove the chosen affix from the list
                 debug!("PREFIX: ----- affix: '{}'", affix);
                 let index = copy.iter().position(|x| x == &affix).unwrap();
@@ -344,7 +341,7 @@ fn create_word(config: &Config, onsets: &Vec<String>, codas: &Vec<String>, affix
             // choose a random suffix
             let mut affix = "".to_owned();
             while !affix.starts_with("-") {
-                affix = copy.choose(&mut rng).unwrap().to_owned();
+                affix = copy.choose(rng).unwrap().to_owned();
                 debug!("SUFFIX: ----- affix: {}", affix);
                 let index = copy.iter().position(|x| x == &affix).unwrap();
                 debug!("index: {}, copy.length(): {}", index, copy.len());
@@ -456,33 +453,100 @@ struct Config {
     pub structures: Vec<String>,
     /// The maximum number of syllables in a word
     pub max_syllable_count: i32,
+    /// An ordered list of sound-change rules applied after a word is built
+    #[serde(default)]
+    pub rules: Vec<String>,
+    /// Regex patterns a generated word must NOT match to be accepted
+    #[serde(default)]
+    pub constraints: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+/// A single generated word in both of its written forms.
+struct Word {
+    /// The IPA transcription
+    ipa: String,
+    /// The romanized spelling
+    romanized: String,
 }
 
 fn main() {
-    // number of words to generate
-    let mut word_count = 5;
-    debug!("word_count:\t{}", word_count);
-    // very basic launch argument handling
-    let args: Vec<String> = env::args().collect();
-    let mut path = String::new();
-    let mut affixes: Vec<String> = vec![];
-    if !handle_launch_args(args, &mut word_count, &mut path, &mut affixes) {
-        return;
-    }
+    let args = Args::parse();
+    DEBUG.store(args.debug, Ordering::Relaxed);
+    debug!("word_count:\t{}", args.count);
 
-    let (config, codas, onsets) = handle_config(path);
+    let (config, codas, onsets) = handle_config(args.path);
     // if the config file is empty, yell at end user and exit
     if config.consonants.len() == 0 || config.vowels.len() == 0 {
         println!("Error: Config file is empty or does not exist");
         return;
     }
 
+    // build the cumulative-weight tables once, up front
+    let onsets = WeightedList::new(&onsets);
+    let codas = WeightedList::new(&codas);
+    let vowels = WeightedList::new(&config.vowels);
+    let structures = WeightedList::new(&config.structures);
+
+    // compile the phonotactic constraints once, up front; a bad pattern is a
+    // config error, so report it and bail rather than panicking with a backtrace
+    let mut constraints: Vec<Regex> = vec![];
+    for pattern in &config.constraints {
+        match Regex::new(pattern) {
+            Ok(compiled) => constraints.push(compiled),
+            Err(err) => {
+                eprintln!("invalid constraint '{}': {}", pattern, err);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // seed the RNG so a given seed always reproduces the same output
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
     // for each word
-    for _ in 0..word_count {
-        let word = create_word(&config, &onsets, &codas, &affixes);
-        // join the word
-        let (ipa_word, romanized_word) = create_final_str(word, &config);
-        // print romanized word
-        println!("{} /{}/", romanized_word, ipa_word);
+    let mut words = vec![];
+    for _ in 0..args.count {
+        let mut result = None;
+        // regenerate until the candidate satisfies every constraint
+        for attempt in 0..100 {
+            let word = create_word(&config, &onsets, &codas, &vowels, &structures, &args.affixes, &mut rng);
+            // run the assembled phoneme sequence through the sound-change rules
+            let word = rules::apply_rules(word, &config);
+            // test the bracketed phoneme string before romanization
+            let candidate = word.join("");
+            if constraints.iter().any(|pattern| pattern.is_match(&candidate).unwrap_or(false)) {
+                debug!("candidate '{}' rejected by constraint (attempt {})", candidate, attempt + 1);
+                continue;
+            }
+            let (ipa, romanized) = create_final_str(word, &config);
+            result = Some(Word { ipa, romanized });
+            break;
+        }
+        match result {
+            Some(word) => words.push(word),
+            None => debug!("no candidate satisfied the constraints after 100 attempts; skipping"),
+        }
+    }
+
+    // emit the words in the requested format
+    match args.format {
+        Format::Plain => {
+            for word in &words {
+                println!("{} /{}/", word.romanized, word.ipa);
+            }
+        }
+        Format::Json => {
+            println!("{}", serde_json::to_string_pretty(&words).unwrap());
+        }
+        Format::Csv => {
+            println!("ipa,romanized");
+            for word in &words {
+                println!("{},{}", word.ipa, word.romanized);
+            }
+        }
     }
 }
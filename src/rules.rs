@@ -0,0 +1,347 @@
+use crate::Config;
+
+/// A single matcher within a rule's target or environment.
+///
+/// The rule syntax only deals in three kinds of unit: a literal phoneme
+/// token (the bracketed `[x]` units the generator already produces), the
+/// two built-in classes `C` and `V`, and the word-boundary marker `#`.
+enum Matcher {
+    /// A literal phoneme, stored without its surrounding brackets.
+    Phoneme(String),
+    /// Any consonant listed in `config.consonants`.
+    Consonant,
+    /// Any vowel listed in `config.vowels`.
+    Vowel,
+    /// The edge of the word (start or end of the phoneme stream).
+    Boundary,
+}
+
+impl Matcher {
+    /// Parses a single whitespace-delimited rule token into a `Matcher`.
+    ///
+    /// Bracketed phonemes are accepted with or without their brackets so
+    /// that rules can be written as either `[p]` or `p`.
+    fn parse(token: &str) -> Matcher {
+        match token {
+            "#" => Matcher::Boundary,
+            "C" => Matcher::Consonant,
+            "V" => Matcher::Vowel,
+            _ => Matcher::Phoneme(token.trim_start_matches('[').trim_end_matches(']').to_string()),
+        }
+    }
+
+    /// Tests whether this matcher accepts the given (unbracketed) phoneme.
+    ///
+    /// A `Boundary` never matches an actual phoneme; it is handled
+    /// separately by the environment scanner.
+    fn matches(&self, phoneme: &str, config: &Config) -> bool {
+        match self {
+            Matcher::Phoneme(p) => p == phoneme,
+            Matcher::Consonant => config.consonants.iter().any(|c| strip_weight(c) == phoneme),
+            Matcher::Vowel => config.vowels.iter().any(|v| strip_weight(v) == phoneme),
+            Matcher::Boundary => false,
+        }
+    }
+}
+
+/// Strips a trailing inline `:<u32>` weight from a config phoneme so the
+/// `C`/`V` classes compare against the bare phoneme the generator emits.
+///
+/// This mirrors the weight parsing in [`crate::weighted::WeightedList`]: only
+/// a numeric suffix is removed, so a phoneme legitimately ending in `:x` is
+/// left untouched.
+fn strip_weight(entry: &str) -> &str {
+    match entry.rsplit_once(':') {
+        Some((item, weight)) if weight.parse::<u32>().is_ok() => item,
+        _ => entry,
+    }
+}
+
+/// A single parsed sound-change rule of the form
+/// `target > replacement / before _ after`.
+struct Rule {
+    target: Vec<Matcher>,
+    replacement: Vec<String>,
+    before: Vec<Matcher>,
+    after: Vec<Matcher>,
+}
+
+impl Rule {
+    /// Parses one rule line, returning `None` when the line is malformed.
+    ///
+    /// The environment (`/ before _ after`) is optional; when it is
+    /// omitted the rule applies unconditionally.
+    fn parse(line: &str) -> Option<Rule> {
+        let (change, environment) = match line.split_once('/') {
+            Some((c, e)) => (c, Some(e)),
+            None => (line, None),
+        };
+        let (target, replacement) = change.split_once('>')?;
+
+        let target: Vec<Matcher> = target.split_whitespace().map(Matcher::parse).collect();
+        if target.is_empty() {
+            return None;
+        }
+        // The replacement is always a sequence of literal phonemes and may be
+        // empty, which expresses deletion.
+        let replacement: Vec<String> = replacement
+            .split_whitespace()
+            .map(|t| t.trim_start_matches('[').trim_end_matches(']').to_string())
+            .collect();
+
+        let (before, after) = match environment {
+            Some(env) => {
+                let (b, a) = env.split_once('_')?;
+                (
+                    b.split_whitespace().map(Matcher::parse).collect(),
+                    a.split_whitespace().map(Matcher::parse).collect(),
+                )
+            }
+            None => (vec![], vec![]),
+        };
+
+        Some(Rule { target, replacement, before, after })
+    }
+}
+
+/// One unit of the flattened word stream.
+///
+/// Syllable and stress markers are carried through untouched so that the
+/// output string is reassembled exactly as the generator intended.
+enum Token {
+    /// A phoneme, stored without its surrounding brackets.
+    Phoneme(String),
+    /// A stress (`'`) or syllable (` `) marker.
+    Marker(String),
+}
+
+/// Splits the generated word into a flat list of phoneme and marker tokens.
+///
+/// The generator concatenates each syllable into a single `String` (e.g.
+/// `"[p][a]"`); this pulls those back apart into individual `[x]` units so
+/// the scanner can address one phoneme at a time.
+fn flatten(word: &[String]) -> Vec<Token> {
+    let mut tokens = vec![];
+    for part in word {
+        if !part.starts_with('[') {
+            tokens.push(Token::Marker(part.clone()));
+            continue;
+        }
+        let mut rest = part.as_str();
+        while let Some(start) = rest.find('[') {
+            if let Some(end) = rest[start..].find(']') {
+                let inner = &rest[start + 1..start + end];
+                tokens.push(Token::Phoneme(inner.to_string()));
+                rest = &rest[start + end + 1..];
+            } else {
+                break;
+            }
+        }
+    }
+    tokens
+}
+
+/// Reassembles a flattened token list into the `Vec<String>` shape the rest
+/// of the pipeline expects, re-wrapping phonemes in their brackets.
+fn unflatten(tokens: Vec<Token>) -> Vec<String> {
+    tokens
+        .into_iter()
+        .map(|t| match t {
+            Token::Phoneme(p) => format!("[{}]", p),
+            Token::Marker(m) => m,
+        })
+        .collect()
+}
+
+/// Tests whether the `before` environment holds immediately to the left of
+/// phoneme `index`, scanning right-to-left and honoring `#` boundaries.
+fn matches_before(phonemes: &[String], index: usize, before: &[Matcher], config: &Config) -> bool {
+    let mut pos = index;
+    for matcher in before.iter().rev() {
+        match matcher {
+            Matcher::Boundary => {
+                if pos != 0 {
+                    return false;
+                }
+            }
+            other => {
+                if pos == 0 || !other.matches(&phonemes[pos - 1], config) {
+                    return false;
+                }
+                pos -= 1;
+            }
+        }
+    }
+    true
+}
+
+/// Tests whether the `after` environment holds immediately to the right of
+/// the target, scanning left-to-right and honoring `#` boundaries.
+fn matches_after(phonemes: &[String], index: usize, after: &[Matcher], config: &Config) -> bool {
+    let mut pos = index;
+    for matcher in after {
+        match matcher {
+            Matcher::Boundary => {
+                if pos != phonemes.len() {
+                    return false;
+                }
+            }
+            other => {
+                if pos >= phonemes.len() || !other.matches(&phonemes[pos], config) {
+                    return false;
+                }
+                pos += 1;
+            }
+        }
+    }
+    true
+}
+
+/// Applies a single rule across the flattened token stream.
+///
+/// Scanning restarts immediately after each substituted region so that a
+/// self-feeding rule cannot loop forever on its own output.
+fn apply_one(tokens: &mut Vec<Token>, rule: &Rule, config: &Config) {
+    let target_len = rule.target.len();
+    let mut cursor = 0;
+    loop {
+        // The flat index of every phoneme, in order, alongside its value.
+        let mut flat_index = vec![];
+        let mut phonemes = vec![];
+        for (i, token) in tokens.iter().enumerate() {
+            if let Token::Phoneme(p) = token {
+                flat_index.push(i);
+                phonemes.push(p.clone());
+            }
+        }
+
+        if cursor + target_len > phonemes.len() {
+            break;
+        }
+
+        let target_matches = rule
+            .target
+            .iter()
+            .enumerate()
+            .all(|(k, m)| m.matches(&phonemes[cursor + k], config));
+
+        if target_matches
+            && matches_before(&phonemes, cursor, &rule.before, config)
+            && matches_after(&phonemes, cursor + target_len, &rule.after, config)
+        {
+            // Remove only the matched phoneme tokens, highest index first so the
+            // lower indices stay valid; any syllable/stress markers that sit
+            // between them are left in place.
+            let matched: Vec<usize> = (cursor..cursor + target_len).map(|k| flat_index[k]).collect();
+            let start = matched[0];
+            for &index in matched.iter().rev() {
+                tokens.remove(index);
+            }
+            // Insert the replacement where the target began, ahead of any
+            // preserved interior markers.
+            let inserted = rule.replacement.len();
+            for (offset, phoneme) in rule.replacement.iter().enumerate() {
+                tokens.insert(start + offset, Token::Phoneme(phoneme.clone()));
+            }
+            // Resume just past the region we just wrote.
+            cursor += inserted;
+        } else {
+            cursor += 1;
+        }
+    }
+}
+
+/// Runs the word's phoneme sequence through the config's ordered list of
+/// sound-change rules.
+///
+/// # Arguments
+///
+/// * `word` - The generated word as a `Vec<String>` of bracketed phonemes
+///   interspersed with stress and syllable markers
+///
+/// * `config` - The configuration loaded from the config file
+///
+/// # Returns
+///
+/// `Vec<String>` - The word after every rule has been applied in order
+///
+/// # Example
+///
+/// ```
+/// // Voice a stop between vowels, then rebuild the word list
+/// let word = apply_rules(word, &config);
+/// ```
+pub fn apply_rules(word: Vec<String>, config: &Config) -> Vec<String> {
+    let mut tokens = flatten(&word);
+    for line in &config.rules {
+        match Rule::parse(line) {
+            Some(rule) => apply_one(&mut tokens, &rule, config),
+            None => debug!("skipping malformed rule: {}", line),
+        }
+    }
+    unflatten(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::apply_rules;
+    use crate::Config;
+    use std::collections::HashMap;
+
+    /// A minimal config carrying just the phoneme inventory and the rules the
+    /// test exercises.
+    fn config(rules: &[&str]) -> Config {
+        Config {
+            consonants: vec!["p".to_string(), "t".to_string(), "b".to_string()],
+            onsets: vec![],
+            codas: vec![],
+            vowels: vec!["a".to_string(), "e".to_string(), "i".to_string()],
+            stressed: 0,
+            romanization: HashMap::new(),
+            structures: vec![],
+            max_syllable_count: 1,
+            rules: rules.iter().map(|r| r.to_string()).collect(),
+            constraints: vec![],
+        }
+    }
+
+    /// Builds a word token list from bare phoneme names.
+    fn word(phonemes: &[&str]) -> Vec<String> {
+        phonemes.iter().map(|p| format!("[{}]", p)).collect()
+    }
+
+    #[test]
+    fn intervocalic_voicing() {
+        let config = config(&["p > b / V _ V"]);
+        assert_eq!(apply_rules(word(&["a", "p", "a"]), &config), word(&["a", "b", "a"]));
+        // no voicing when the environment does not hold
+        assert_eq!(apply_rules(word(&["p", "a"]), &config), word(&["p", "a"]));
+    }
+
+    #[test]
+    fn empty_replacement_deletes() {
+        let config = config(&["t > / V _ #"]);
+        assert_eq!(apply_rules(word(&["a", "t"]), &config), word(&["a"]));
+    }
+
+    #[test]
+    fn word_boundary_anchor() {
+        let config = config(&["a > e / # _"]);
+        // only the word-initial vowel is rewritten
+        assert_eq!(apply_rules(word(&["a", "t", "a"]), &config), word(&["e", "t", "a"]));
+    }
+
+    #[test]
+    fn class_matching() {
+        let config = config(&["V > i"]);
+        assert_eq!(apply_rules(word(&["a", "p", "e"]), &config), word(&["i", "p", "i"]));
+    }
+
+    #[test]
+    fn interior_marker_is_preserved() {
+        // the target spans a stress marker, which must survive the substitution
+        let config = config(&["a t > o"]);
+        let input = vec!["[a]".to_string(), "'".to_string(), "[t]".to_string()];
+        assert_eq!(apply_rules(input, &config), vec!["[o]".to_string(), "'".to_string()]);
+    }
+}
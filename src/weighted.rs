@@ -0,0 +1,72 @@
+use rand::Rng;
+
+/// A list of selectable items with a precomputed cumulative-weight table.
+///
+/// Each entry in a config array may carry an optional relative weight using
+/// the inline `item:weight` syntax (e.g. `"t:5"`, `"ʒ:1"`). Entries written
+/// without a weight default to `1`, so a list with no weights at all behaves
+/// exactly like the old uniform `SliceRandom::choose`.
+///
+/// Only a trailing `:<u32>` is treated as a weight; an item whose suffix after
+/// the last `:` does not parse as a number is kept whole, so a phoneme that
+/// genuinely ends in `:x` is preserved rather than silently corrupted.
+///
+/// The prefix sums are built once when the list is constructed, so a draw is
+/// a single `rng.gen_range` plus a binary search — `O(log n)` with no
+/// per-call allocation.
+pub struct WeightedList {
+    /// The selectable items, stripped of any inline weight suffix.
+    items: Vec<String>,
+    /// Prefix sums of the weights; `cumulative[i]` is the running total
+    /// through `items[i]`.
+    cumulative: Vec<u32>,
+    /// The sum of every weight, i.e. the exclusive upper bound for a draw.
+    total: u32,
+}
+
+impl WeightedList {
+    /// Builds a weighted list from a raw config array.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - The config entries, each either `"item"` or `"item:weight"`
+    ///
+    /// # Returns
+    ///
+    /// `WeightedList` - A list ready for `O(log n)` weighted sampling
+    pub fn new(raw: &[String]) -> WeightedList {
+        let mut items = vec![];
+        let mut cumulative = vec![];
+        let mut total = 0;
+        for entry in raw {
+            let (item, weight) = match entry.rsplit_once(':') {
+                // a trailing `:<u32>` is a weight; anything else is part of the item
+                Some((item, weight)) => match weight.parse::<u32>() {
+                    Ok(weight) => (item, weight),
+                    Err(_) => (entry.as_str(), 1),
+                },
+                None => (entry.as_str(), 1),
+            };
+            total += weight;
+            items.push(item.to_string());
+            cumulative.push(total);
+        }
+        WeightedList { items, cumulative, total }
+    }
+
+    /// Draws one item according to its relative weight.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - The random number generator
+    ///
+    /// # Returns
+    ///
+    /// `&str` - The chosen item, without any inline weight suffix
+    pub fn choose<R: Rng + ?Sized>(&self, rng: &mut R) -> &str {
+        let roll = rng.gen_range(0..self.total);
+        // First index whose running total exceeds the roll.
+        let index = self.cumulative.partition_point(|&c| c <= roll);
+        &self.items[index]
+    }
+}